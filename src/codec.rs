@@ -0,0 +1,241 @@
+//! A small, higher-level codec surface built on top of the prefix-varint primitives.
+//!
+//! [`Encode`]/[`Decode`] turn `prefix_varint` from "one integer at a time" into a building block
+//! for deriving compact message formats: integer primitives are coded with the existing
+//! prefix-varint routines, and collections (`Vec<T>`, `&[T]`), `Option<T>`, and tuples get blanket
+//! impls that compose those primitive impls. Collections are framed as a prefix-uvarint length
+//! followed by the elements ([`crate::GroupVarintBuf`] and [`crate::DeltaVarintBuf`] store no
+//! length and leave framing to the caller, but a length-prefixed message format needs it built
+//! in).
+//!
+//! Decoding a length-prefixed collection validates the length against the buffer's remaining
+//! capacity up front and returns [`DecodeError::LengthOverflow`] rather than acting on an
+//! untrusted length (e.g. `Vec::with_capacity(huge_len)`).
+use crate::{VarintBuf, VarintBufMut, VarintError};
+use bytes::buf::{Buf, BufMut};
+
+/// Error returned by [`Decode::decode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// Decoding a prefix varint failed; see [`VarintError`].
+    Varint(VarintError),
+    /// A collection's length prefix claimed more elements than the buffer could possibly hold.
+    LengthOverflow {
+        /// The decoded element count.
+        len: usize,
+        /// Bytes remaining in the buffer at the point the length was read.
+        remaining: usize,
+    },
+}
+
+impl From<VarintError> for DecodeError {
+    fn from(e: VarintError) -> Self {
+        DecodeError::Varint(e)
+    }
+}
+
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecodeError::Varint(e) => write!(f, "{e}"),
+            DecodeError::LengthOverflow { len, remaining } => write!(
+                f,
+                "length prefix {len} exceeds {remaining} remaining bytes"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
+
+/// A value that can be written to a `bytes::BufMut` using the prefix-varint coding scheme.
+pub trait Encode {
+    /// Encodes `self` into `buf`.
+    fn encode<B: BufMut + ?Sized>(&self, buf: &mut B);
+}
+
+/// A value that can be read from a `bytes::Buf` using the prefix-varint coding scheme.
+pub trait Decode: Sized {
+    /// Decodes a value from `buf`.
+    fn decode<B: Buf + ?Sized>(buf: &mut B) -> Result<Self, DecodeError>;
+}
+
+macro_rules! impl_codec_for_int {
+    ($ty:ty, $put:ident, $try_get:ident) => {
+        impl Encode for $ty {
+            fn encode<B: BufMut + ?Sized>(&self, buf: &mut B) {
+                buf.$put(*self);
+            }
+        }
+
+        impl Decode for $ty {
+            fn decode<B: Buf + ?Sized>(buf: &mut B) -> Result<Self, DecodeError> {
+                Ok(buf.$try_get()?)
+            }
+        }
+    };
+}
+
+impl_codec_for_int!(u64, put_prefix_uvarint, try_get_prefix_uvarint);
+impl_codec_for_int!(i64, put_prefix_varint, try_get_prefix_varint);
+
+// Wide integers decode through the consuming `get_prefix_*` routines: every failure, including a
+// truncated value, is reported as `VarintError::Eof`, and the buffer may have been advanced.
+macro_rules! impl_codec_for_wide_int {
+    ($ty:ty, $put:ident, $get:ident) => {
+        impl Encode for $ty {
+            fn encode<B: BufMut + ?Sized>(&self, buf: &mut B) {
+                buf.$put(*self);
+            }
+        }
+
+        impl Decode for $ty {
+            fn decode<B: Buf + ?Sized>(buf: &mut B) -> Result<Self, DecodeError> {
+                buf.$get().ok_or(DecodeError::Varint(VarintError::Eof))
+            }
+        }
+    };
+}
+
+impl_codec_for_wide_int!(u128, put_prefix_u128, get_prefix_u128);
+impl_codec_for_wide_int!(i128, put_prefix_i128, get_prefix_i128);
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode<B: BufMut + ?Sized>(&self, buf: &mut B) {
+        match self {
+            Some(v) => {
+                buf.put_u8(1);
+                v.encode(buf);
+            }
+            None => buf.put_u8(0),
+        }
+    }
+}
+
+impl<T: Decode> Decode for Option<T> {
+    fn decode<B: Buf + ?Sized>(buf: &mut B) -> Result<Self, DecodeError> {
+        if !buf.has_remaining() {
+            return Err(DecodeError::Varint(VarintError::Eof));
+        }
+        match buf.get_u8() {
+            0 => Ok(None),
+            _ => Ok(Some(T::decode(buf)?)),
+        }
+    }
+}
+
+impl<T: Encode> Encode for &[T] {
+    fn encode<B: BufMut + ?Sized>(&self, buf: &mut B) {
+        buf.put_prefix_uvarint(self.len() as u64);
+        for item in self.iter() {
+            item.encode(buf);
+        }
+    }
+}
+
+macro_rules! impl_codec_for_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: Encode),+> Encode for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn encode<Buf_: BufMut + ?Sized>(&self, buf: &mut Buf_) {
+                let ($($name,)+) = self;
+                $($name.encode(buf);)+
+            }
+        }
+
+        impl<$($name: Decode),+> Decode for ($($name,)+) {
+            fn decode<Buf_: Buf + ?Sized>(buf: &mut Buf_) -> Result<Self, DecodeError> {
+                Ok(($($name::decode(buf)?,)+))
+            }
+        }
+    };
+}
+
+impl_codec_for_tuple!(A);
+impl_codec_for_tuple!(A, B);
+impl_codec_for_tuple!(A, B, C);
+impl_codec_for_tuple!(A, B, C, D);
+
+#[cfg(feature = "std")]
+impl<T: Encode> Encode for Vec<T> {
+    fn encode<B: BufMut + ?Sized>(&self, buf: &mut B) {
+        self.as_slice().encode(buf);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Decode> Decode for Vec<T> {
+    fn decode<B: Buf + ?Sized>(buf: &mut B) -> Result<Self, DecodeError> {
+        let raw_len = u64::decode(buf)?;
+        let remaining = buf.remaining();
+        if raw_len > remaining as u64 {
+            return Err(DecodeError::LengthOverflow {
+                len: raw_len as usize,
+                remaining,
+            });
+        }
+        // `raw_len <= remaining`, which already fits `usize`, so this narrowing is lossless.
+        let len = raw_len as usize;
+
+        let mut out = Vec::with_capacity(len);
+        for _ in 0..len {
+            out.push(T::decode(buf)?);
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn roundtrip_vec_u64() {
+        let vals: Vec<u64> = vec![0, 1, 0x7f, 0x3fff, u64::MAX];
+        let mut buf = Vec::new();
+        vals.encode(&mut buf);
+
+        let mut rbuf = buf.as_slice();
+        assert_eq!(Vec::<u64>::decode(&mut rbuf).unwrap(), vals);
+        assert!(!rbuf.has_remaining());
+    }
+
+    #[test]
+    fn roundtrip_option() {
+        let mut buf = Vec::new();
+        Some(42i64).encode(&mut buf);
+        None::<i64>.encode(&mut buf);
+
+        let mut rbuf = buf.as_slice();
+        assert_eq!(Option::<i64>::decode(&mut rbuf).unwrap(), Some(42));
+        assert_eq!(Option::<i64>::decode(&mut rbuf).unwrap(), None);
+    }
+
+    #[test]
+    fn roundtrip_tuple() {
+        let mut buf = Vec::new();
+        (1u64, -2i64, 3u64).encode(&mut buf);
+
+        let mut rbuf = buf.as_slice();
+        assert_eq!(
+            <(u64, i64, u64)>::decode(&mut rbuf).unwrap(),
+            (1u64, -2i64, 3u64)
+        );
+    }
+
+    #[test]
+    fn decode_vec_rejects_overlong_length() {
+        let mut buf = Vec::new();
+        buf.put_prefix_uvarint(1_000_000);
+
+        let mut rbuf = buf.as_slice();
+        assert_eq!(
+            Vec::<u64>::decode(&mut rbuf),
+            Err(DecodeError::LengthOverflow {
+                len: 1_000_000,
+                remaining: 0,
+            })
+        );
+    }
+}
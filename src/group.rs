@@ -0,0 +1,171 @@
+//! A batch-oriented companion to the per-value [`crate::VarintBuf`]/[`crate::VarintBufMut`]
+//! coding scheme.
+//!
+//! Coding one `u64` at a time with [`crate::VarintBufMut::put_prefix_uvarint`] spends a branch
+//! per value choosing how many bytes to write. When coding a whole `&[u64]` at once (the common
+//! "encode a column of integers" case) that per-value branching dominates the cost. This module
+//! instead processes values in groups of 4 using a Stream-VByte-style layout: the *lengths* of
+//! the values are pulled out into control bytes, and the significant bytes of the values are
+//! packed back to back after them. Because `u64` needs up to 8 bytes to represent (as opposed to
+//! 4 for `u32`, where the classic 2-bit-per-value control byte comes from), each length is
+//! instead stored as a 4-bit nibble (`byte_len - 1`, covering lengths 1-8), and one control byte
+//! packs the nibbles for a pair of values. A group of 4 values therefore emits 2 pairs, each a
+//! control byte followed by the minimal little-endian representation of its two values; control
+//! and data bytes share one interleaved stream, with no separate control stream.
+//!
+//! Unlike [`crate::VarintBuf::get_prefix_uvarint`], decoding here does not discover the element
+//! count from the stream: the caller must know how many values to read, exactly as `out.len()`
+//! does for [`GroupVarintBuf::get_group_uvarint`].
+use bytes::buf::{Buf, BufMut};
+
+/// Number of values coded together as one group.
+const GROUP_SIZE: usize = 4;
+
+/// Minimal number of little-endian bytes needed to represent `v`, in `1..=8`.
+#[inline]
+fn value_len(v: u64) -> u8 {
+    if v == 0 {
+        1
+    } else {
+        (64 - v.leading_zeros()).div_ceil(8) as u8
+    }
+}
+
+/// Packs a pair of 1-8 byte lengths into a single control byte as two 4-bit nibbles.
+#[inline]
+fn pack_lens(len0: u8, len1: u8) -> u8 {
+    ((len0 - 1) << 4) | (len1 - 1)
+}
+
+/// Unpacks a control byte into a pair of 1-8 byte lengths.
+#[inline]
+fn unpack_lens(ctrl: u8) -> (u8, u8) {
+    ((ctrl >> 4) + 1, (ctrl & 0xf) + 1)
+}
+
+fn put_pair<B: BufMut + ?Sized>(b: &mut B, v0: u64, v1: u64) {
+    let len0 = value_len(v0);
+    let len1 = value_len(v1);
+    b.put_u8(pack_lens(len0, len1));
+    b.put_slice(&v0.to_le_bytes()[..len0 as usize]);
+    b.put_slice(&v1.to_le_bytes()[..len1 as usize]);
+}
+
+fn get_pair<B: Buf + ?Sized>(b: &mut B, out0: &mut u64, out1: &mut u64) {
+    let (len0, len1) = unpack_lens(b.get_u8());
+    *out0 = b.get_uint_le(len0 as usize);
+    *out1 = b.get_uint_le(len1 as usize);
+}
+
+/// An extension to the `bytes::BufMut` trait to add group-varint batch encoding.
+pub trait GroupVarintBufMut: bytes::BufMut {
+    /// Encodes every value in `vals` into the buffer using the group-varint layout.
+    ///
+    /// `vals` is processed 4 values at a time; a trailing group of 1-3 values is coded as pairs,
+    /// with a lone last value padded by a zero to make a pair. The caller must track `vals.len()`
+    /// themselves to decode it back with [`GroupVarintBuf::get_group_uvarint`], since no length
+    /// is stored.
+    fn put_group_uvarint(&mut self, vals: &[u64]) {
+        let mut chunks = vals.chunks_exact(GROUP_SIZE);
+        for group in &mut chunks {
+            put_pair(self, group[0], group[1]);
+            put_pair(self, group[2], group[3]);
+        }
+
+        let tail = chunks.remainder();
+        let mut pairs = tail.chunks_exact(2);
+        for pair in &mut pairs {
+            put_pair(self, pair[0], pair[1]);
+        }
+        if let [last] = pairs.remainder() {
+            put_pair(self, *last, 0);
+        }
+    }
+}
+
+impl<B: BufMut + ?Sized> GroupVarintBufMut for B {}
+
+/// An extension to the `bytes::Buf` trait to add group-varint batch decoding.
+pub trait GroupVarintBuf: bytes::Buf {
+    /// Decodes `out.len()` values from the buffer that were encoded with
+    /// [`GroupVarintBufMut::put_group_uvarint`].
+    ///
+    /// This may panic if the buffer does not contain enough bytes to fill `out`.
+    fn get_group_uvarint(&mut self, out: &mut [u64]) {
+        let mut chunks = out.chunks_exact_mut(GROUP_SIZE);
+        for group in &mut chunks {
+            let (g01, g23) = group.split_at_mut(2);
+            let (v0, v1) = g01.split_at_mut(1);
+            get_pair(self, &mut v0[0], &mut v1[0]);
+            let (v2, v3) = g23.split_at_mut(1);
+            get_pair(self, &mut v2[0], &mut v3[0]);
+        }
+
+        let tail = chunks.into_remainder();
+        let mut pairs = tail.chunks_exact_mut(2);
+        for pair in &mut pairs {
+            let (v0, v1) = pair.split_at_mut(1);
+            get_pair(self, &mut v0[0], &mut v1[0]);
+        }
+        if let [last] = pairs.into_remainder() {
+            let mut discard = 0u64;
+            get_pair(self, last, &mut discard);
+        }
+    }
+}
+
+impl<B: Buf + ?Sized> GroupVarintBuf for B {}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+
+    fn roundtrip(vals: &[u64]) {
+        let mut buf: Vec<u8> = Vec::new();
+        buf.put_group_uvarint(vals);
+
+        let mut out = vec![0u64; vals.len()];
+        buf.as_slice().get_group_uvarint(&mut out);
+        assert_eq!(vals, out.as_slice());
+    }
+
+    #[test]
+    fn empty() {
+        roundtrip(&[]);
+    }
+
+    #[test]
+    fn single_group() {
+        roundtrip(&[0, 0x7f, 0x3fff, 0xffffffffffffffff]);
+    }
+
+    #[test]
+    fn tail_one() {
+        roundtrip(&[1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn tail_two() {
+        roundtrip(&[1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn tail_three() {
+        roundtrip(&[1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn all_widths() {
+        roundtrip(&[
+            0x0,
+            0x7f,
+            0x3fff,
+            0x1fffff,
+            0xfffffff,
+            0x7ffffffff,
+            0x3ffffffffff,
+            0x1ffffffffffff,
+            0xffffffffffffffff,
+        ]);
+    }
+}
@@ -1,3 +1,5 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
 //! This module implements a prefix-based variable length integer coding scheme.
 //!
 //! Unlike an [LEB128](https://en.wikipedia.org/wiki/LEB128)-style encoding scheme, this encoding
@@ -14,7 +16,12 @@
 //! implemented for common in-memory byte stream types. Lower level methods that operate directly
 //! on pointers are also provided but come with caveats (may overread/overwrite).
 //!
+//! This crate is `no_std` (but not allocation-free: `bytes` itself needs `alloc` for types like
+//! `BytesMut`) unless the default `std` feature is enabled.
+//!
 //! ```
+//! # #[cfg(feature = "std")]
+//! # {
 //! use bytes::Buf;
 //! use prefix_varint::{VarintBuf, VarintBufMut};
 //!
@@ -29,9 +36,17 @@
 //!   assert_eq!(buf.get_prefix_uvarint(), Some(v));
 //! }
 //! assert!(!buf.has_remaining());
+//! # }
 //! ```
 use bytes::buf::{Buf, BufMut};
 
+mod codec;
+mod delta;
+mod group;
+pub use codec::{Decode, DecodeError, Encode};
+pub use delta::{DeltaVarintBuf, DeltaVarintBufMut};
+pub use group::{GroupVarintBuf, GroupVarintBufMut};
+
 /// Maximum number of bytes a single encoded uvarint will occupy.
 pub const MAX_LEN: usize = 9;
 
@@ -76,35 +91,35 @@ const TAG_PREFIX: [u64; 9] = [
 unsafe fn encode_prefix_uvarint_slow(v: u64, p: *mut u8) -> usize {
     if v <= MAX_VALUE[2] {
         let tv = (v | TAG_PREFIX[2]) as u16;
-        std::ptr::write_unaligned(p as *mut u16, tv.to_be());
+        core::ptr::write_unaligned(p as *mut u16, tv.to_be());
         2
     } else if v <= MAX_VALUE[3] {
         let tv = ((v | TAG_PREFIX[3]) << 8) as u32;
-        std::ptr::write_unaligned(p as *mut u32, tv.to_be());
+        core::ptr::write_unaligned(p as *mut u32, tv.to_be());
         3
     } else if v <= MAX_VALUE[4] {
         let tv = (v | TAG_PREFIX[4]) as u32;
-        std::ptr::write_unaligned(p as *mut u32, tv.to_be());
+        core::ptr::write_unaligned(p as *mut u32, tv.to_be());
         4
     } else if v <= MAX_VALUE[5] {
         let tv = (v | TAG_PREFIX[5]) << 24;
-        std::ptr::write_unaligned(p as *mut u64, tv.to_be());
+        core::ptr::write_unaligned(p as *mut u64, tv.to_be());
         5
     } else if v <= MAX_VALUE[6] {
         let tv = (v | TAG_PREFIX[6]) << 16;
-        std::ptr::write_unaligned(p as *mut u64, tv.to_be());
+        core::ptr::write_unaligned(p as *mut u64, tv.to_be());
         6
     } else if v <= MAX_VALUE[7] {
         let tv = (v | TAG_PREFIX[7]) << 8;
-        std::ptr::write_unaligned(p as *mut u64, tv.to_be());
+        core::ptr::write_unaligned(p as *mut u64, tv.to_be());
         7
     } else if v <= MAX_VALUE[8] {
         let tv = v | TAG_PREFIX[8];
-        std::ptr::write_unaligned(p as *mut u64, tv.to_be());
+        core::ptr::write_unaligned(p as *mut u64, tv.to_be());
         8
     } else {
-        std::ptr::write(p, u8::MAX);
-        std::ptr::write_unaligned(p.add(1) as *mut u64, v.to_be());
+        core::ptr::write(p, u8::MAX);
+        core::ptr::write_unaligned(p.add(1) as *mut u64, v.to_be());
         9
     }
 }
@@ -115,7 +130,7 @@ unsafe fn encode_prefix_uvarint_slow(v: u64, p: *mut u8) -> usize {
 #[inline]
 pub unsafe fn encode_prefix_uvarint(v: u64, p: *mut u8) -> usize {
     if v <= MAX_VALUE[1] {
-        std::ptr::write(p, v as u8);
+        core::ptr::write(p, v as u8);
         1
     } else {
         encode_prefix_uvarint_slow(v, p)
@@ -130,6 +145,92 @@ pub unsafe fn encode_prefix_varint(v: i64, p: *mut u8) -> usize {
     encode_prefix_uvarint(zigzag_encode(v), p)
 }
 
+/// Maximum number of bytes a single encoded u128 prefix varint will occupy.
+pub const MAX_LEN_U128: usize = 19;
+
+/// Maps negative values to positive values for `i128`, mirroring `zigzag_encode`.
+fn zigzag_encode128(v: i128) -> u128 {
+    ((v >> 127) ^ (v << 1)) as u128
+}
+
+/// Inverts `zigzag_encode128()`.
+fn zigzag_decode128(v: u128) -> i128 {
+    (v >> 1) as i128 ^ -(v as i128 & 1)
+}
+
+/// Number of big-endian bytes beyond the first 8 needed to represent `v`, in `1..=8`.
+///
+/// Only called for `v > u64::MAX`; the first 8 bytes are always present in the wide tier, see
+/// [`encode_prefix_u128`].
+fn u128_extra_len(v: u128) -> u8 {
+    let total_bytes = ((128 - v.leading_zeros()) as usize).div_ceil(8);
+    (total_bytes - 8) as u8
+}
+
+/// Packs `extra` (`1..=8`) as a unary prefix byte: `extra` leading one bits followed by zeros,
+/// e.g. `extra == 1` packs to `0b1000_0000`. [`unpack_length_byte`] inverts this via
+/// `u8::leading_ones`, the same trick the base 64-bit tiers use for their tag byte.
+fn pack_length_byte(extra: u8) -> u8 {
+    if extra == 8 {
+        u8::MAX
+    } else {
+        !(0xffu8 >> extra)
+    }
+}
+
+/// Inverts [`pack_length_byte`].
+fn unpack_length_byte(b: u8) -> u8 {
+    b.leading_ones() as u8
+}
+
+/// Encodes `v` as a prefix u128 to `p`.
+///
+/// Values that fit in a `u64` are encoded identically to [`encode_prefix_uvarint`]. Wider values
+/// extend the existing tag-byte scheme with a second tier: the all-ones tag byte (`u8::MAX`) is
+/// followed by a `0x00` sentinel byte -- a pattern [`encode_prefix_uvarint`] never produces for
+/// any `u64`, since its own all-ones tag byte is always followed by the *literal* big-endian
+/// encoding of a value `>= 2^56`, whose first byte can never be zero -- then a length byte (see
+/// [`pack_length_byte`]) giving the extra byte count beyond 8, then the big-endian payload.
+///
+/// Note this is a different wire format than a literal "unary length byte straight after the
+/// saturated tag" would give: that scheme would reuse the same `0xff` tag byte as the existing
+/// 9-byte `u64` catch-all (see [`encode_prefix_uvarint_slow`]) with no way to tell the two tiers
+/// apart, breaking the promise that the 64-bit encoding is a strict prefix of this one. The
+/// `0x00` sentinel disambiguates them up front, at the cost of one extra header byte.
+///
+/// This may write up to `MAX_LEN_U128` bytes and may panic if fewer bytes are available.
+///
+/// # Safety
+///
+/// `p` must be valid for writes of up to `MAX_LEN_U128` bytes.
+#[inline]
+pub unsafe fn encode_prefix_u128(v: u128, p: *mut u8) -> usize {
+    if v <= u64::MAX as u128 {
+        encode_prefix_uvarint(v as u64, p)
+    } else {
+        let extra = u128_extra_len(v);
+        let total = 8 + extra as usize;
+        core::ptr::write(p, u8::MAX);
+        core::ptr::write(p.add(1), 0u8);
+        core::ptr::write(p.add(2), pack_length_byte(extra));
+        let be = v.to_be_bytes();
+        core::ptr::copy_nonoverlapping(be.as_ptr().add(16 - total), p.add(3), total);
+        3 + total
+    }
+}
+
+/// Encodes `v` as a prefix i128 to `p`.
+///
+/// This may write up to `MAX_LEN_U128` bytes and may panic if fewer bytes are available.
+///
+/// # Safety
+///
+/// `p` must be valid for writes of up to `MAX_LEN_U128` bytes.
+#[inline]
+pub unsafe fn encode_prefix_i128(v: i128, p: *mut u8) -> usize {
+    encode_prefix_u128(zigzag_encode128(v), p)
+}
+
 fn put_prefix_uvarint_slow<B: bytes::BufMut + ?Sized>(b: &mut B, v: u64) {
     if v < MAX_VALUE[2] {
         b.put_u16((v | TAG_PREFIX[2]) as u16)
@@ -174,6 +275,38 @@ pub trait VarintBufMut: bytes::BufMut {
     fn put_prefix_varint(&mut self, v: i64) {
         self.put_prefix_uvarint(zigzag_encode(v))
     }
+
+    /// Puts `v` into the buffer in a variable length encoding using 1-19 bytes.
+    ///
+    /// Values that fit in a `u64` use exactly the same encoding as `put_prefix_uvarint`.
+    #[inline]
+    fn put_prefix_u128(&mut self, v: u128) {
+        if v <= u64::MAX as u128 {
+            return self.put_prefix_uvarint(v as u64);
+        }
+
+        let buf = self.chunk_mut();
+        if buf.len() >= MAX_LEN_U128 {
+            unsafe {
+                let len = encode_prefix_u128(v, buf.as_mut_ptr());
+                self.advance_mut(len);
+            }
+        } else {
+            let extra = u128_extra_len(v);
+            let total = 8 + extra as usize;
+            self.put_u8(u8::MAX);
+            self.put_u8(0);
+            self.put_u8(pack_length_byte(extra));
+            let be = v.to_be_bytes();
+            self.put_slice(&be[16 - total..]);
+        }
+    }
+
+    /// Puts `v` into the buffer in a variable length encoding using 1-19 bytes.
+    #[inline]
+    fn put_prefix_i128(&mut self, v: i128) {
+        self.put_prefix_u128(zigzag_encode128(v))
+    }
 }
 
 // Implement for all tyeps that implement BufMut
@@ -183,36 +316,36 @@ unsafe fn decode_prefix_uvarint_slow(tag: u8, p: *const u8) -> (u64, usize) {
     let (raw, len) = match tag.leading_ones() {
         // NB: zero is handled by decode_prefix_uvarint().
         1 => (
-            u64::from(u16::from_be(std::ptr::read_unaligned(p as *const u16))) & MAX_VALUE[2],
+            u64::from(u16::from_be(core::ptr::read_unaligned(p as *const u16))) & MAX_VALUE[2],
             2,
         ),
         2 => (
-            u64::from(u32::from_be(std::ptr::read_unaligned(p as *const u32)) >> 8) & MAX_VALUE[3],
+            u64::from(u32::from_be(core::ptr::read_unaligned(p as *const u32)) >> 8) & MAX_VALUE[3],
             3,
         ),
         3 => (
-            u64::from(u32::from_be(std::ptr::read_unaligned(p as *const u32))) & MAX_VALUE[4],
+            u64::from(u32::from_be(core::ptr::read_unaligned(p as *const u32))) & MAX_VALUE[4],
             4,
         ),
         4 => (
-            (u64::from_be(std::ptr::read_unaligned(p as *const u64)) >> 24) & MAX_VALUE[5],
+            (u64::from_be(core::ptr::read_unaligned(p as *const u64)) >> 24) & MAX_VALUE[5],
             5,
         ),
         5 => (
-            (u64::from_be(std::ptr::read_unaligned(p as *const u64)) >> 16) & MAX_VALUE[6],
+            (u64::from_be(core::ptr::read_unaligned(p as *const u64)) >> 16) & MAX_VALUE[6],
             6,
         ),
         6 => (
-            (u64::from_be(std::ptr::read_unaligned(p as *const u64)) >> 8) & MAX_VALUE[7],
+            (u64::from_be(core::ptr::read_unaligned(p as *const u64)) >> 8) & MAX_VALUE[7],
             7,
         ),
         7 => (
-            u64::from_be(std::ptr::read_unaligned(p as *const u64)) & MAX_VALUE[8],
+            u64::from_be(core::ptr::read_unaligned(p as *const u64)) & MAX_VALUE[8],
             8,
         ),
         // NB: this is a catch-all but the maximum possible value for tag.leading_ones() is 8.
         _ => (
-            u64::from_be(std::ptr::read_unaligned(p.add(1) as *const u64)),
+            u64::from_be(core::ptr::read_unaligned(p.add(1) as *const u64)),
             9,
         ),
     };
@@ -226,7 +359,7 @@ const MAX_1BYTE_TAG: u8 = MAX_VALUE[1] as u8;
 /// This function may read up to `MAX_LEN` bytes from `p` and may panic if fewer bytes are available.
 #[inline]
 pub unsafe fn decode_prefix_uvarint(p: *const u8) -> (u64, usize) {
-    let tag = std::ptr::read(p);
+    let tag = core::ptr::read(p);
     if tag <= MAX_1BYTE_TAG {
         return (tag.into(), 1);
     } else {
@@ -243,6 +376,44 @@ pub unsafe fn decode_prefix_varint(p: *const u8) -> (i64, usize) {
     (zigzag_decode(v), len)
 }
 
+/// Decodes a prefix u128 value from `p`, returning the value and the number of bytes consumed.
+///
+/// This function may read up to `MAX_LEN_U128` bytes from `p` and may panic if fewer bytes are
+/// available. See [`encode_prefix_u128`] for the wide-tier layout.
+///
+/// # Safety
+///
+/// `p` must be valid for reads of up to `MAX_LEN_U128` bytes.
+#[inline]
+pub unsafe fn decode_prefix_u128(p: *const u8) -> (u128, usize) {
+    let tag = core::ptr::read(p);
+    if tag != u8::MAX || core::ptr::read(p.add(1)) != 0 {
+        // Either a short tier, or the tier-1 catch-all (a literal u64 whose top byte is nonzero).
+        let (v, len) = decode_prefix_uvarint(p);
+        return (u128::from(v), len);
+    }
+
+    let extra = unpack_length_byte(core::ptr::read(p.add(2)));
+    let total = 8 + extra as usize;
+    let mut bytes = [0u8; 16];
+    core::ptr::copy_nonoverlapping(p.add(3), bytes.as_mut_ptr().add(16 - total), total);
+    (u128::from_be_bytes(bytes), 3 + total)
+}
+
+/// Decodes a prefix i128 value from `p`, returning the value and the number of bytes consumed.
+///
+/// This function may read up to `MAX_LEN_U128` bytes from `p` and may panic if fewer bytes are
+/// available.
+///
+/// # Safety
+///
+/// `p` must be valid for reads of up to `MAX_LEN_U128` bytes.
+#[inline]
+pub unsafe fn decode_prefix_i128(p: *const u8) -> (i128, usize) {
+    let (v, len) = decode_prefix_u128(p);
+    (zigzag_decode128(v), len)
+}
+
 fn get_prefix_uvarint_slow<B: Buf + ?Sized>(b: &mut B, tag: u8) -> Option<u64> {
     let remaining_bytes = tag.leading_ones() as usize;
     if b.remaining() < remaining_bytes {
@@ -263,6 +434,39 @@ fn get_prefix_uvarint_slow<B: Buf + ?Sized>(b: &mut B, tag: u8) -> Option<u64> {
     Some(raw)
 }
 
+/// Error returned by the fallible, non-consuming `try_get_prefix_*` decoders.
+///
+/// Unlike `get_prefix_uvarint`'s `None`, this distinguishes a clean stream boundary from a value
+/// that is only partially buffered so far, and never advances the buffer on failure, which makes
+/// it safe to retry once more bytes have arrived.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VarintError {
+    /// The buffer had no bytes remaining.
+    Eof,
+    /// A tag byte was present but there weren't enough trailing bytes to decode the value yet.
+    Truncated {
+        /// Total bytes (including the tag) the encoded value will occupy once fully buffered.
+        needed: usize,
+        /// Bytes currently available in the buffer.
+        available: usize,
+    },
+}
+
+impl core::fmt::Display for VarintError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            VarintError::Eof => write!(f, "no bytes remaining"),
+            VarintError::Truncated { needed, available } => write!(
+                f,
+                "truncated varint: needed {needed} bytes, only {available} available"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VarintError {}
+
 /// An extension to the `bytes::Buf` trait to add prefix varint decoding methods.
 pub trait VarintBuf: bytes::Buf {
     /// Reads a single prefix uvarint value from the buffer.
@@ -293,12 +497,109 @@ pub trait VarintBuf: bytes::Buf {
         let v = self.get_prefix_uvarint()?;
         Some(zigzag_decode(v))
     }
+
+    /// Reads a single prefix uvarint value from the buffer without consuming it on failure.
+    ///
+    /// Returns `VarintError::Eof` if the buffer is empty, or `VarintError::Truncated` if a tag
+    /// byte is present but the value's remaining bytes haven't all arrived yet. In both error
+    /// cases the buffer position is left unchanged, so the caller can retry this same read once
+    /// more bytes are available, which `get_prefix_uvarint` does not support.
+    #[inline]
+    fn try_get_prefix_uvarint(&mut self) -> Result<u64, VarintError> {
+        let buf = self.chunk();
+        if buf.len() >= MAX_LEN {
+            let (value, len) = unsafe { decode_prefix_uvarint(buf.as_ptr()) };
+            self.advance(len);
+            return Ok(value);
+        }
+        if buf.is_empty() {
+            return Err(VarintError::Eof);
+        }
+
+        let tag = buf[0];
+        if tag <= MAX_1BYTE_TAG {
+            self.advance(1);
+            return Ok(tag.into());
+        }
+
+        let needed = 1 + tag.leading_ones() as usize;
+        let available = self.remaining();
+        if available < needed {
+            return Err(VarintError::Truncated { needed, available });
+        }
+
+        self.advance(1);
+        Ok(get_prefix_uvarint_slow(self, tag).expect("remaining bytes already checked"))
+    }
+
+    /// Reads a single prefix varint value from the buffer without consuming it on failure.
+    ///
+    /// See `try_get_prefix_uvarint` for the non-consuming error semantics.
+    #[inline]
+    fn try_get_prefix_varint(&mut self) -> Result<i64, VarintError> {
+        self.try_get_prefix_uvarint().map(zigzag_decode)
+    }
+
+    /// Reads a single prefix u128 value from the buffer.
+    /// If the input is not long enough to produce a value, advances to the end and returns `None`.
+    #[inline]
+    fn get_prefix_u128(&mut self) -> Option<u128> {
+        let buf = self.chunk();
+        if buf.len() >= MAX_LEN_U128 {
+            let (value, len) = unsafe { decode_prefix_u128(buf.as_ptr()) };
+            self.advance(len);
+            return Some(value);
+        }
+        if !self.has_remaining() {
+            return None;
+        }
+
+        let tag = self.get_u8();
+        if tag != u8::MAX {
+            return if tag <= MAX_1BYTE_TAG {
+                Some(u128::from(tag))
+            } else {
+                get_prefix_uvarint_slow(self, tag).map(u128::from)
+            };
+        }
+        if !self.has_remaining() {
+            return None;
+        }
+
+        // Peek, don't consume: a nonzero continuation byte belongs to the tier-1 catch-all's
+        // literal payload, which get_prefix_uvarint_slow needs to read from the start.
+        if self.chunk()[0] != 0 {
+            return get_prefix_uvarint_slow(self, tag).map(u128::from);
+        }
+        self.advance(1);
+
+        if !self.has_remaining() {
+            return None;
+        }
+        let extra = unpack_length_byte(self.get_u8());
+        let total = 8 + extra as usize;
+        if self.remaining() < total {
+            self.advance(self.remaining());
+            return None;
+        }
+        let mut bytes = [0u8; 16];
+        self.copy_to_slice(&mut bytes[16 - total..]);
+        Some(u128::from_be_bytes(bytes))
+    }
+
+    /// Reads a single prefix i128 value from the buffer.
+    /// If the input is not long enough to produce a value, advances to the end and returns `None`.
+    #[inline]
+    fn get_prefix_i128(&mut self) -> Option<i128> {
+        let v = self.get_prefix_u128()?;
+        Some(zigzag_decode128(v))
+    }
 }
 
 // Implement for all types that implement Buf.
 impl<B: Buf + ?Sized> VarintBuf for B {}
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod test {
     use super::*;
     use rand::distributions::Uniform;
@@ -479,4 +780,87 @@ mod test {
             assert_eq!(trunc.get_prefix_uvarint(), None, "{}", *v);
         }
     }
+
+    #[test]
+    fn try_decode_empty_is_eof() {
+        assert_eq!([].as_slice().try_get_prefix_uvarint(), Err(VarintError::Eof));
+    }
+
+    #[test]
+    fn try_decode_truncated_does_not_advance() {
+        // Skip the 1-byte tier: truncating a 1-byte encoding leaves an empty buffer, which is
+        // Eof rather than Truncated (there's no tag left to signal a partial value).
+        for v in MAX_VALUE.iter().skip(2) {
+            let mut buf = Vec::new();
+            buf.put_prefix_uvarint(*v);
+            let full_len = buf.len();
+            let mut trunc = &buf[0..(full_len - 1)];
+            assert_eq!(
+                trunc.try_get_prefix_uvarint(),
+                Err(VarintError::Truncated {
+                    needed: full_len,
+                    available: full_len - 1,
+                }),
+                "{}",
+                *v
+            );
+            // The error must not have consumed any bytes, so the full buffer is still there.
+            assert_eq!(trunc.remaining(), full_len - 1);
+        }
+    }
+
+    #[test]
+    fn try_decode_succeeds_once_complete() {
+        for v in MAX_VALUE.iter().skip(1) {
+            let mut buf = Vec::new();
+            buf.put_prefix_uvarint(*v);
+            let mut rest = buf.as_slice();
+            assert_eq!(rest.try_get_prefix_uvarint(), Ok(*v));
+            assert!(!rest.has_remaining());
+        }
+    }
+
+    fn roundtrip_u128(v: u128) {
+        let mut buf = Vec::new();
+        buf.put_prefix_u128(v);
+        assert_eq!(Some(v), buf.as_slice().get_prefix_u128(), "{v:#x}");
+    }
+
+    #[test]
+    fn u128_fits_in_u64_matches_uvarint_encoding() {
+        for v in MAX_VALUE.iter() {
+            let mut wide = Vec::new();
+            wide.put_prefix_u128(*v as u128);
+            let mut narrow = Vec::new();
+            narrow.put_prefix_uvarint(*v);
+            assert_eq!(wide, narrow, "{v:#x}");
+        }
+    }
+
+    #[test]
+    fn u128_boundaries() {
+        roundtrip_u128(0);
+        roundtrip_u128(u64::MAX as u128);
+        roundtrip_u128(u64::MAX as u128 + 1);
+        roundtrip_u128(u128::MAX);
+    }
+
+    #[test]
+    fn u128_random() {
+        let mut rng = StdRng::from_seed([0xcdu8; 32]);
+        for _ in 0..RANDOM_TEST_LEN {
+            roundtrip_u128(rng.gen());
+        }
+    }
+
+    #[test]
+    fn i128_random() {
+        let mut rng = StdRng::from_seed([0xefu8; 32]);
+        for _ in 0..RANDOM_TEST_LEN {
+            let v: i128 = rng.gen();
+            let mut buf = Vec::new();
+            buf.put_prefix_i128(v);
+            assert_eq!(Some(v), buf.as_slice().get_prefix_i128(), "{v:#x}");
+        }
+    }
 }